@@ -0,0 +1,496 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use pathdiff::diff_paths;
+use roxmltree::{Document, Node};
+use serde_derive::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+/// A single source -> destination file mapping produced by an installer,
+/// both paths relative to the mod's extracted directory and the game's
+/// install directory respectively.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMapping {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupType {
+    SelectExactlyOne,
+    SelectAtMostOne,
+    SelectAtLeastOne,
+    SelectAny,
+    SelectAll,
+}
+
+impl GroupType {
+    fn parse(s: &str) -> Self {
+        match s {
+            "SelectExactlyOne" => GroupType::SelectExactlyOne,
+            "SelectAtMostOne" => GroupType::SelectAtMostOne,
+            "SelectAtLeastOne" => GroupType::SelectAtLeastOne,
+            "SelectAll" => GroupType::SelectAll,
+            _ => GroupType::SelectAny,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Plugin {
+    name: String,
+    description: String,
+    files: Vec<FileMapping>,
+    flags: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+    name: String,
+    ty: GroupType,
+    plugins: Vec<Plugin>,
+}
+
+#[derive(Debug, Clone)]
+struct InstallStep {
+    name: String,
+    groups: Vec<Group>,
+}
+
+#[derive(Debug, Clone)]
+struct ConditionalInstall {
+    flags: Vec<(String, String)>,
+    files: Vec<FileMapping>,
+}
+
+#[derive(Debug, Clone)]
+struct ModuleConfig {
+    #[allow(dead_code)]
+    module_name: String,
+    required_files: Vec<FileMapping>,
+    steps: Vec<InstallStep>,
+    conditional_installs: Vec<ConditionalInstall>,
+}
+
+/// Parse and interactively run the FOMOD installer at `config_path`,
+/// returning the file mappings the user's choices produced.
+pub fn run(extracted_root: &Path, config_path: &Path) -> crate::Result<Vec<FileMapping>> {
+    let xml = fs::read_to_string(config_path)?;
+    let doc = Document::parse(&xml)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let module = parse_module_config(doc.root_element(), extracted_root);
+
+    println!("Installing {}", module.module_name);
+    let mut files = module.required_files;
+    let mut flags: HashMap<String, String> = HashMap::new();
+
+    for step in &module.steps {
+        println!("== {} ==", step.name);
+        for group in &step.groups {
+            for idx in prompt_group(group)? {
+                let plugin = &group.plugins[idx];
+                files.extend(plugin.files.iter().cloned());
+                for (k, v) in &plugin.flags {
+                    flags.insert(k.clone(), v.clone());
+                }
+            }
+        }
+    }
+
+    for cond in &module.conditional_installs {
+        if cond
+            .flags
+            .iter()
+            .all(|(k, v)| flags.get(k).map_or(false, |set| set == v))
+        {
+            files.extend(cond.files.iter().cloned());
+        }
+    }
+
+    Ok(files)
+}
+
+fn prompt_group(group: &Group) -> crate::Result<Vec<usize>> {
+    println!("{} ({:?}):", group.name, group.ty);
+    for (i, plugin) in group.plugins.iter().enumerate() {
+        println!("  [{}] {} - {}", i + 1, plugin.name, plugin.description);
+    }
+    let read_line = || -> crate::Result<String> {
+        print!("> ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        Ok(input.trim().to_string())
+    };
+    match group.ty {
+        GroupType::SelectAll => Ok((0..group.plugins.len()).collect()),
+        GroupType::SelectExactlyOne => loop {
+            if let Ok(i) = read_line()?.parse::<usize>() {
+                if i >= 1 && i <= group.plugins.len() {
+                    break Ok(vec![i - 1]);
+                }
+            }
+            println!("Enter a number between 1 and {}", group.plugins.len());
+        },
+        GroupType::SelectAtMostOne => {
+            let input = read_line()?;
+            if input.is_empty() {
+                Ok(Vec::new())
+            } else {
+                Ok(input
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|i| *i >= 1 && *i <= group.plugins.len())
+                    .map(|i| vec![i - 1])
+                    .unwrap_or_default())
+            }
+        }
+        GroupType::SelectAtLeastOne | GroupType::SelectAny => loop {
+            let chosen: Vec<usize> = read_line()?
+                .split(',')
+                .filter_map(|s| s.trim().parse::<usize>().ok())
+                .filter(|i| *i >= 1 && *i <= group.plugins.len())
+                .map(|i| i - 1)
+                .collect();
+            if group.ty == GroupType::SelectAny || !chosen.is_empty() {
+                break Ok(chosen);
+            }
+            println!("Select at least one option");
+        },
+    }
+}
+
+fn parse_module_config(root: Node, extracted_root: &Path) -> ModuleConfig {
+    let module_name = child(root, "moduleName")
+        .and_then(|n| n.text())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let required_files = child(root, "requiredInstallFiles")
+        .map(|n| parse_files(n, extracted_root))
+        .unwrap_or_default();
+    let steps = child(root, "installSteps")
+        .map(|steps| {
+            children(steps, "installStep")
+                .map(|s| parse_step(s, extracted_root))
+                .collect()
+        })
+        .unwrap_or_default();
+    let conditional_installs = child(root, "conditionalFileInstalls")
+        .and_then(|n| child(n, "patterns"))
+        .map(|patterns| {
+            children(patterns, "pattern")
+                .filter_map(|p| parse_conditional(p, extracted_root))
+                .collect()
+        })
+        .unwrap_or_default();
+    ModuleConfig {
+        module_name,
+        required_files,
+        steps,
+        conditional_installs,
+    }
+}
+
+fn parse_step(node: Node, extracted_root: &Path) -> InstallStep {
+    let name = node.attribute("name").unwrap_or_default().to_string();
+    let groups = child(node, "optionalFileGroups")
+        .map(|g| {
+            children(g, "group")
+                .map(|grp| parse_group(grp, extracted_root))
+                .collect()
+        })
+        .unwrap_or_default();
+    InstallStep { name, groups }
+}
+
+fn parse_group(node: Node, extracted_root: &Path) -> Group {
+    let name = node.attribute("name").unwrap_or_default().to_string();
+    let ty = GroupType::parse(node.attribute("type").unwrap_or("SelectAny"));
+    let plugins = child(node, "plugins")
+        .map(|p| {
+            children(p, "plugin")
+                .map(|pl| parse_plugin(pl, extracted_root))
+                .collect()
+        })
+        .unwrap_or_default();
+    Group { name, ty, plugins }
+}
+
+fn parse_plugin(node: Node, extracted_root: &Path) -> Plugin {
+    let name = node.attribute("name").unwrap_or_default().to_string();
+    let description = child(node, "description")
+        .and_then(|n| n.text())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let files = child(node, "files")
+        .map(|f| parse_files(f, extracted_root))
+        .unwrap_or_default();
+    let flags = child(node, "conditionFlags")
+        .map(|f| {
+            children(f, "flag")
+                .filter_map(|fl| {
+                    let name = fl.attribute("name")?.to_string();
+                    Some((name, fl.text().unwrap_or_default().to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Plugin {
+        name,
+        description,
+        files,
+        flags,
+    }
+}
+
+fn parse_conditional(node: Node, extracted_root: &Path) -> Option<ConditionalInstall> {
+    let deps = child(node, "dependencies")?;
+    let flags = children(deps, "flagDependency")
+        .filter_map(|d| {
+            let name = d.attribute("flag")?.to_string();
+            let value = d.attribute("value").unwrap_or_default().to_string();
+            Some((name, value))
+        })
+        .collect();
+    let files = child(node, "files")
+        .map(|f| parse_files(f, extracted_root))
+        .unwrap_or_default();
+    Some(ConditionalInstall { flags, files })
+}
+
+fn parse_files(node: Node, extracted_root: &Path) -> Vec<FileMapping> {
+    node.children()
+        .filter(|n| n.has_tag_name("file") || n.has_tag_name("folder"))
+        .flat_map(|n| {
+            let source = n.attribute("source").unwrap_or_default();
+            let destination = n.attribute("destination").unwrap_or(source);
+            resolve_entry(extracted_root, source, destination)
+        })
+        .collect()
+}
+
+/// Resolve a FOMOD `source` path (backslash-separated, case-insensitive)
+/// against the extracted mod tree, expanding directories to every file they
+/// contain and remapping each to its destination.
+fn resolve_entry(extracted_root: &Path, source: &str, destination: &str) -> Vec<FileMapping> {
+    let rel_source = normalize_separators(source);
+    let Some(rel_destination) = sanitize_destination(&normalize_separators(destination)) else {
+        return Vec::new();
+    };
+    let Some(abs_source) = find_case_insensitive(extracted_root, &rel_source) else {
+        return Vec::new();
+    };
+    if abs_source.is_dir() {
+        WalkDir::new(&abs_source)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| {
+                let rel = diff_paths(e.path(), &abs_source)?;
+                Some(FileMapping {
+                    source: diff_paths(e.path(), extracted_root)?,
+                    destination: rel_destination.join(rel),
+                })
+            })
+            .collect()
+    } else {
+        match diff_paths(&abs_source, extracted_root) {
+            Some(source) => vec![FileMapping {
+                source,
+                destination: rel_destination,
+            }],
+            None => Vec::new(),
+        }
+    }
+}
+
+fn normalize_separators(path: &str) -> PathBuf {
+    PathBuf::from(path.replace('\\', "/"))
+}
+
+/// Reject a mod-supplied destination path that would escape the install
+/// root - `..` components or an absolute/prefixed path - returning the
+/// cleaned relative path when it's safe to join onto an install directory.
+/// Both the FOMOD parser and anything that later joins a `FileMapping`'s
+/// destination onto an install path (deploy, plugin/load-order discovery)
+/// should go through this rather than trusting the path as-is.
+pub fn sanitize_destination(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Walk `rel` component by component under `root`, matching each component
+/// case-insensitively since FOMOD source paths don't respect the case of the
+/// archive they came from.
+fn find_case_insensitive(root: &Path, rel: &Path) -> Option<PathBuf> {
+    let mut current = root.to_path_buf();
+    for component in rel.components() {
+        let name = component.as_os_str().to_string_lossy();
+        let entry = fs::read_dir(&current)
+            .ok()?
+            .filter_map(Result::ok)
+            .find(|e| e.file_name().to_string_lossy().eq_ignore_ascii_case(&name))?;
+        current = entry.path();
+    }
+    Some(current)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    tag: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |n| n.has_tag_name(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE_CONFIG: &str = r#"
+        <config>
+            <moduleName>Test Mod</moduleName>
+            <requiredInstallFiles>
+                <file source="Always.esp" destination="Always.esp" />
+            </requiredInstallFiles>
+            <installSteps>
+                <installStep name="Choose a texture pack">
+                    <optionalFileGroups>
+                        <group name="Textures" type="SelectExactlyOne">
+                            <plugins>
+                                <plugin name="HD">
+                                    <description>High-res textures</description>
+                                    <files>
+                                        <folder source="HD" destination="textures" />
+                                    </files>
+                                    <conditionFlags>
+                                        <flag name="textures">hd</flag>
+                                    </conditionFlags>
+                                </plugin>
+                            </plugins>
+                        </group>
+                    </optionalFileGroups>
+                </installStep>
+            </installSteps>
+            <conditionalFileInstalls>
+                <patterns>
+                    <pattern>
+                        <dependencies>
+                            <flagDependency flag="textures" value="hd" />
+                        </dependencies>
+                        <files>
+                            <file source="HD.esp" destination="HD.esp" />
+                        </files>
+                    </pattern>
+                </patterns>
+            </conditionalFileInstalls>
+        </config>
+    "#;
+
+    #[test]
+    fn parses_module_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("HD")).unwrap();
+        fs::write(dir.path().join("HD").join("texture.dds"), b"").unwrap();
+        fs::write(dir.path().join("Always.esp"), b"").unwrap();
+        fs::write(dir.path().join("HD.esp"), b"").unwrap();
+
+        let doc = Document::parse(MODULE_CONFIG).unwrap();
+        let module = parse_module_config(doc.root_element(), dir.path());
+
+        assert_eq!(module.module_name, "Test Mod");
+        assert_eq!(
+            module.required_files,
+            vec![FileMapping {
+                source: PathBuf::from("Always.esp"),
+                destination: PathBuf::from("Always.esp"),
+            }]
+        );
+        assert_eq!(module.steps.len(), 1);
+        let group = &module.steps[0].groups[0];
+        assert_eq!(group.ty, GroupType::SelectExactlyOne);
+        assert_eq!(
+            group.plugins[0].flags,
+            vec![("textures".into(), "hd".into())]
+        );
+        assert_eq!(
+            group.plugins[0].files,
+            vec![FileMapping {
+                source: PathBuf::from("HD/texture.dds"),
+                destination: PathBuf::from("textures/texture.dds"),
+            }]
+        );
+
+        assert_eq!(module.conditional_installs.len(), 1);
+        let conditional = &module.conditional_installs[0];
+        assert_eq!(conditional.flags, vec![("textures".into(), "hd".into())]);
+        assert_eq!(
+            conditional.files,
+            vec![FileMapping {
+                source: PathBuf::from("HD.esp"),
+                destination: PathBuf::from("HD.esp"),
+            }]
+        );
+    }
+
+    #[test]
+    fn resolve_entry_finds_files_case_insensitively() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("Textures")).unwrap();
+        fs::write(dir.path().join("Textures").join("a.dds"), b"").unwrap();
+
+        let mappings = resolve_entry(dir.path(), "textures\\a.dds", "Data\\textures\\a.dds");
+        assert_eq!(
+            mappings,
+            vec![FileMapping {
+                source: PathBuf::from("Textures/a.dds"),
+                destination: PathBuf::from("Data/textures/a.dds"),
+            }]
+        );
+    }
+
+    #[test]
+    fn resolve_entry_rejects_escaping_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.esp"), b"").unwrap();
+
+        let mappings = resolve_entry(dir.path(), "a.esp", "..\\..\\a.esp");
+        assert!(mappings.is_empty());
+    }
+
+    #[test]
+    fn sanitize_destination_rejects_traversal_and_absolute_paths() {
+        assert_eq!(
+            sanitize_destination(Path::new("textures/a.dds")),
+            Some(PathBuf::from("textures/a.dds"))
+        );
+        assert_eq!(sanitize_destination(Path::new("../../etc/passwd")), None);
+        assert_eq!(sanitize_destination(Path::new("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn find_case_insensitive_matches_differing_case() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("Meshes")).unwrap();
+        fs::write(dir.path().join("Meshes").join("Thing.nif"), b"").unwrap();
+
+        let found = find_case_insensitive(dir.path(), Path::new("meshes/thing.nif"));
+        assert_eq!(found, Some(dir.path().join("Meshes").join("Thing.nif")));
+    }
+}
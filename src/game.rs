@@ -1,13 +1,15 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs::{self, File},
-    io::Write,
+    io::{self, Write},
     path::{Path, PathBuf},
     process::Command,
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 use indexmap::IndexMap;
 use pathdiff::diff_paths;
+use rayon::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 use walkdir::{DirEntry, WalkDir};
 
@@ -56,6 +58,9 @@ impl GlobalConfig {
                 plugins_file: plugins,
                 deployment: DeploymentMethod::default(),
                 mods: IndexMap::new(),
+                deployed: Vec::new(),
+                load_order: Vec::new(),
+                active_profile: None,
             },
         }
         .save()?;
@@ -96,7 +101,12 @@ pub struct ManagedMod {
     pub enabled: bool,
     pub extracted: Option<PathBuf>,
     pub archive: PathBuf,
-    pub parts: Vec<PathBuf>,
+    /// The file mappings an installer produced, if one has run for this
+    /// mod. `Some(vec![])` means the installer ran and the user selected
+    /// nothing; `None` means no installer has run yet and `file_mappings`
+    /// should fall back to mirroring the extracted tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<fomod::FileMapping>>,
 }
 
 impl ManagedMod {
@@ -106,16 +116,44 @@ impl ManagedMod {
             ..Self::default()
         }
     }
-    pub fn part_paths(&self) -> Vec<PathBuf> {
-        if self.parts.is_empty() {
-            if let Some(extr) = &self.extracted {
-                vec![extr.clone()]
-            } else {
-                Vec::new()
+    /// The file mappings to deploy for this mod, falling back to a straight
+    /// mirror of the extracted directory when no installer (FOMOD or
+    /// otherwise) has run for it yet.
+    ///
+    /// Destinations are re-sanitized here rather than trusted as stored:
+    /// `files` is attacker/mod-controlled data that round-trips through
+    /// `climm.toml`, and this is the single choke point every caller
+    /// (`install`, `plan_deployment`, `sync_load_order`) goes through, so
+    /// it's the one place that needs to reject an escaping path.
+    pub fn file_mappings(&self) -> crate::Result<Vec<fomod::FileMapping>> {
+        if let Some(files) = &self.files {
+            return Ok(files
+                .iter()
+                .filter_map(|mapping| {
+                    let destination = fomod::sanitize_destination(&mapping.destination)?;
+                    Some(fomod::FileMapping {
+                        source: mapping.source.clone(),
+                        destination,
+                    })
+                })
+                .collect());
+        }
+        let extracted = match &self.extracted {
+            Some(extracted) => extracted,
+            None => return Ok(Vec::new()),
+        };
+        let mut mappings = Vec::new();
+        for entry in WalkDir::new(extracted) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let rel = diff_paths(entry.path(), extracted).unwrap();
+                mappings.push(fomod::FileMapping {
+                    source: rel.clone(),
+                    destination: rel,
+                });
             }
-        } else {
-            self.parts.clone()
         }
+        Ok(mappings)
     }
 }
 
@@ -123,6 +161,7 @@ impl ManagedMod {
 pub enum DeploymentMethod {
     Hardlink,
     Symlink,
+    Copy,
 }
 
 impl Default for DeploymentMethod {
@@ -139,6 +178,36 @@ pub struct Config {
     pub plugins_file: Option<PathBuf>,
     pub deployment: DeploymentMethod,
     pub mods: IndexMap<String, ManagedMod>,
+    /// Install-relative paths climm created on the last successful deploy,
+    /// so the next deploy can uninstall precisely those files instead of
+    /// re-walking every mod's extracted directory.
+    pub deployed: Vec<PathBuf>,
+    /// The explicit plugin load order, independent of mod order. Newly
+    /// discovered plugins are added by `write_plugins`: masters right after
+    /// the last stored master, other plugins at the end.
+    pub load_order: Vec<PluginEntry>,
+    /// The currently active profile, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+}
+
+/// A single plugin's position and enabled state within the load order,
+/// independent of whether the mod that owns it is enabled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginEntry {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// A named snapshot of which mods are enabled, their order, and the plugin
+/// load order, so a game can be switched between setups without
+/// re-adding mods. The underlying archives and extracted data are shared
+/// from the library and aren't duplicated per profile.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub mod_order: Vec<String>,
+    pub enabled: HashMap<String, bool>,
+    pub load_order: Vec<PluginEntry>,
 }
 
 pub struct Game {
@@ -146,12 +215,89 @@ pub struct Game {
     pub config: Config,
 }
 
+/// The result of resolving every enabled mod's file mappings into the set
+/// of files that will actually be deployed.
+struct DeploymentPlan {
+    /// Every normalized destination path mapped to the mods that provide
+    /// it, in `config.mods` order. The last entry is the winner.
+    providers: IndexMap<PathBuf, Vec<String>>,
+    /// The winning (source, destination) pair for each normalized
+    /// destination path.
+    winners: IndexMap<PathBuf, (PathBuf, PathBuf)>,
+}
+
+/// Whether the target filesystem treats paths as case-insensitive, and so
+/// whether destination paths need lowercasing before being used as conflict
+/// keys.
+#[cfg(any(windows, target_os = "macos"))]
+fn case_insensitive_fs() -> bool {
+    true
+}
+#[cfg(not(any(windows, target_os = "macos")))]
+fn case_insensitive_fs() -> bool {
+    false
+}
+
+fn normalize_destination(path: &Path) -> PathBuf {
+    if case_insensitive_fs() {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Whether `e` is the OS reporting that a hard link can't cross filesystem
+/// boundaries (`EXDEV` on Unix, `ERROR_NOT_SAME_DEVICE` on Windows).
+fn is_cross_device(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        matches!(e.raw_os_error(), Some(18)) // EXDEV
+    }
+    #[cfg(windows)]
+    {
+        matches!(e.raw_os_error(), Some(17)) // ERROR_NOT_SAME_DEVICE
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        false
+    }
+}
+
+/// The plugin file name a destination path refers to, if it has a
+/// recognized plugin extension.
+fn plugin_file_name(path: &Path) -> Option<String> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    if ["esp", "esm", "esl"].contains(&ext.as_str()) {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    } else {
+        None
+    }
+}
+
+/// Masters (`.esm`) and light masters (`.esl`) must load before regular
+/// `.esp` plugins.
+fn is_master(name: &str) -> bool {
+    let ext = Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    ext == "esm" || ext == "esl"
+}
+
 const GAME_CONFIG_FILE: &str = "climm.toml";
+const PROFILES_DIR: &str = "profiles";
 
 fn game_config_file(name: &str) -> crate::Result<PathBuf> {
     library::game_dir(name).map(|game_dir| game_dir.join(GAME_CONFIG_FILE))
 }
 
+fn profile_file(name: &str, profile: &str) -> crate::Result<PathBuf> {
+    Ok(library::game_dir(name)?
+        .join(PROFILES_DIR)
+        .join(format!("{}.toml", profile)))
+}
+
 impl Game {
     pub fn config_file(&self) -> crate::Result<PathBuf> {
         game_config_file(&self.name)
@@ -202,44 +348,106 @@ impl Game {
         }
         Ok(())
     }
+    /// Extract every enabled, not-yet-extracted mod. The `7z` child
+    /// processes are run concurrently (bounded by rayon's thread pool)
+    /// since extraction is otherwise dominated by waiting on each one in
+    /// turn.
     fn extract(&mut self) -> crate::Result<()> {
-        for (mod_name, mm) in &mut self.config.mods {
-            if mm.enabled && mm.extracted.is_none() {
-                let extracted_dir = library::extracted_dir(&self.name, mod_name)?;
+        let to_extract = self
+            .config
+            .mods
+            .iter()
+            .filter(|(_, mm)| mm.enabled && mm.extracted.is_none())
+            .map(|(mod_name, mm)| {
+                library::extracted_dir(&self.name, mod_name)
+                    .map(|dir| (mod_name.clone(), mm.archive.clone(), dir))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let results: Vec<(String, PathBuf, io::Result<bool>)> = to_extract
+            .into_par_iter()
+            .map(|(mod_name, archive, extracted_dir)| {
                 utils::print_erasable(&format!("Extracting {:?}...", mod_name));
-                if Command::new("7z")
+                let result = Command::new("7z")
                     .arg("x")
-                    .arg(&mm.archive)
+                    .arg(&archive)
                     .arg(format!("-o{}", extracted_dir.to_string_lossy()))
-                    .output()?
-                    .status
-                    .success()
-                {
-                    mm.extracted = Some(extracted_dir);
+                    .output()
+                    .map(|output| output.status.success());
+                (mod_name, extracted_dir, result)
+            })
+            .collect();
+
+        let mut errors = Vec::new();
+        for (mod_name, extracted_dir, result) in results {
+            match result {
+                Ok(true) => {
+                    if let Some(mm) = self.config.mods.get_mut(&mod_name) {
+                        mm.extracted = Some(extracted_dir);
+                    }
                     println!("Extracted {:?} ", mod_name);
                 }
+                Ok(false) => errors.push((mod_name, "7z exited with a non-zero status".into())),
+                // The 7z process itself couldn't be run, e.g. the binary is
+                // missing - distinct from it running and failing.
+                Err(e) => errors.push((mod_name, e.to_string())),
+            }
+        }
+        if !errors.is_empty() {
+            println!("{} mod(s) failed to extract:", errors.len());
+            for (mod_name, message) in &errors {
+                println!("  {:?}: {}", mod_name, message);
             }
         }
         Ok(())
     }
+    /// Remove exactly the files climm recorded creating on the last
+    /// successful deploy, then prune every empty directory under the
+    /// install directory. This is precise where re-walking each mod's extracted
+    /// directory isn't: it can't clobber a file a still-enabled mod also
+    /// owns, and it handles FOMOD-mapped destinations correctly.
     fn uninstall(&mut self) -> crate::Result<()> {
         let install_dir = self.install_dir();
-        for (_, mm) in &mut self.config.mods {
-            if let Some(extracted_dir) = &mm.extracted {
-                let extraced_diff = differ(&extracted_dir);
-                for entry in WalkDir::new(&extracted_dir) {
-                    let file_entry = entry?;
-                    utils::remove_path(&install_dir, extraced_diff(&file_entry.path()).unwrap())?;
-                }
+        let paths: Vec<PathBuf> = self.config.deployed.drain(..).collect();
+        let results: Vec<(PathBuf, Option<crate::Error>)> = paths
+            .into_par_iter()
+            .map(|path| {
+                let error = utils::remove_path(&install_dir, path.clone()).err();
+                (path, error)
+            })
+            .collect();
+        // Paths that failed to delete stay recorded in `deployed` so a
+        // later uninstall retries them instead of losing track of files
+        // still on disk.
+        let mut errors = Vec::new();
+        for (path, error) in results {
+            if let Some(e) = error {
+                errors.push((path.clone(), e));
+                self.config.deployed.push(path);
             }
         }
+        if !errors.is_empty() {
+            println!("{} file(s) failed to uninstall:", errors.len());
+            for (path, e) in &errors {
+                println!("  {}: {}", path.display(), e);
+            }
+        }
+        prune_empty_dirs(&install_dir)?;
         Ok(())
     }
-    fn install(&mut self) -> crate::Result<()> {
-        let install_dir = self.install_dir();
-        for (mod_name, mm) in &mut self.config.mods {
-            if let (Some(extracted_dir), true) = (&mm.extracted, mm.enabled) {
-                let config = WalkDir::new(&extracted_dir)
+    /// Run the FOMOD installer for every enabled, extracted mod that
+    /// doesn't have resolved file mappings yet, persisting the user's
+    /// selections into `mm.files`. This is the only place that prompts
+    /// interactively, kept separate from `plan_deployment` so read-only
+    /// queries like `conflicts` stay free of side effects.
+    fn resolve_installers(&mut self) -> crate::Result<()> {
+        for (_, mm) in &mut self.config.mods {
+            let extracted_dir = match (&mm.extracted, mm.enabled) {
+                (Some(dir), true) => dir.clone(),
+                _ => continue,
+            };
+            if mm.files.is_none() {
+                let module_config = WalkDir::new(&extracted_dir)
                     .into_iter()
                     .filter_map(Result::ok)
                     .find(|entry| {
@@ -249,69 +457,300 @@ impl Game {
                             .map_or(false, |name| name == "ModuleConfig.xml")
                     })
                     .map(DirEntry::into_path);
-                let install_folders = if !mm.parts.is_empty() {
-                    mm.parts.clone()
-                } else if config.is_some() {
-                    println!(
-                        "{:?} has a Fomod installer, but climm does not currently support it. \
-                        You can still select which sections you want to install.",
-                        mod_name
-                    );
-                    let paths = fomod::pseudo_fomod(&extracted_dir)?;
-                    mm.parts = paths.clone();
-                    paths
-                } else {
-                    vec![extracted_dir.clone()]
-                };
-                // For each folder
-                for folder in install_folders {
-                    let folder_diff = differ(&folder);
-                    // For each file
-                    for entry in WalkDir::new(&folder) {
-                        let file_entry = entry?;
-                        if file_entry.file_type().is_file() {
-                            let extracted_path =
-                                folder.join(folder_diff(&file_entry.path()).unwrap());
-                            let install_path =
-                                install_dir.join(folder_diff(&file_entry.path()).unwrap());
-                            utils::create_dirs(&install_path)?;
-                            // Deploy
-                            match self.config.deployment {
-                                DeploymentMethod::Hardlink => {
-                                    fs::hard_link(extracted_path, install_path)?
-                                }
-                                DeploymentMethod::Symlink => {
-                                    #[cfg(unix)]
-                                    std::os::unix::fs::symlink(extracted_path, install_path)?;
-                                    #[cfg(windows)]
-                                    std::os::windows::fs::hardlink(extracted_path, install_path)?;
-                                }
-                            }
+                if let Some(config_path) = module_config {
+                    mm.files = Some(fomod::run(&extracted_dir, &config_path)?);
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Walk every enabled mod's file mappings and work out, for each
+    /// normalized destination path, which mods provide it and which one
+    /// wins (the last in `config.mods` order, i.e. highest priority). Mods
+    /// whose installer hasn't been resolved yet (see `resolve_installers`)
+    /// fall back to `file_mappings`'s raw mirror of the extracted tree, so
+    /// this never prompts and never writes to `config`.
+    fn plan_deployment(&self) -> crate::Result<DeploymentPlan> {
+        let mut providers: IndexMap<PathBuf, Vec<String>> = IndexMap::new();
+        let mut winners: IndexMap<PathBuf, (PathBuf, PathBuf)> = IndexMap::new();
+        for (mod_name, mm) in &self.config.mods {
+            let extracted_dir = match (&mm.extracted, mm.enabled) {
+                (Some(dir), true) => dir.clone(),
+                _ => continue,
+            };
+            for mapping in mm.file_mappings()? {
+                let normalized = normalize_destination(&mapping.destination);
+                let mods = providers.entry(normalized.clone()).or_default();
+                // A mod mapping several sources to one destination is still
+                // a single provider, not a conflict with itself.
+                if mods.last() != Some(mod_name) {
+                    mods.push(mod_name.clone());
+                }
+                winners.insert(
+                    normalized,
+                    (extracted_dir.join(&mapping.source), mapping.destination),
+                );
+            }
+        }
+        Ok(DeploymentPlan { providers, winners })
+    }
+    /// The destination paths contested by more than one enabled mod, mapped
+    /// to every providing mod in load order (the last one wins). Read-only:
+    /// it never runs a pending FOMOD installer or touches `config`, so it's
+    /// safe to call just to inspect the current setup.
+    pub fn conflicts(&self) -> crate::Result<IndexMap<PathBuf, Vec<String>>> {
+        Ok(self
+            .plan_deployment()?
+            .providers
+            .into_iter()
+            .filter(|(_, mods)| mods.len() > 1)
+            .collect())
+    }
+    fn report_conflicts(providers: &IndexMap<PathBuf, Vec<String>>) {
+        let mut header_printed = false;
+        for (path, mods) in providers {
+            if mods.len() > 1 {
+                if !header_printed {
+                    println!("Resolving conflicts:");
+                    header_printed = true;
+                }
+                println!(
+                    "  {}: {} (winner: {})",
+                    path.display(),
+                    mods.join(", "),
+                    mods.last().unwrap()
+                );
+            }
+        }
+    }
+    /// Deploy the winning file of every destination in the plan in parallel.
+    /// A failing job doesn't stop the others from running, so every error
+    /// is collected and reported together - but the run as a whole is
+    /// still all-or-nothing: if anything failed, every file this run
+    /// created is rolled back and `config.deployed` is left untouched,
+    /// preserving the same transactional guarantee as a serial install.
+    fn install(&mut self) -> crate::Result<()> {
+        let install_dir = self.install_dir();
+        self.resolve_installers()?;
+        let plan = self.plan_deployment()?;
+        Self::report_conflicts(&plan.providers);
+        let deployment = self.config.deployment;
+
+        let jobs: Vec<(PathBuf, PathBuf, PathBuf)> = plan
+            .winners
+            .into_values()
+            .map(|(source, destination)| {
+                let install_path = install_dir.join(&destination);
+                (source, destination, install_path)
+            })
+            .collect();
+
+        // Directory creation isn't safe to race: dedupe and create every
+        // destination's parent up front instead of inside the parallel loop.
+        let mut parent_dirs: Vec<&Path> = jobs
+            .iter()
+            .filter_map(|(_, _, install_path)| install_path.parent())
+            .collect();
+        parent_dirs.sort();
+        parent_dirs.dedup();
+        for dir in parent_dirs {
+            fs::create_dir_all(dir)?;
+        }
+
+        let warned_cross_device = AtomicBool::new(false);
+        let results: Vec<(PathBuf, crate::Result<()>)> = jobs
+            .into_par_iter()
+            .map(|(source, destination, install_path)| {
+                let result =
+                    Self::deploy_file(&source, &install_path, deployment, &warned_cross_device);
+                (destination, result)
+            })
+            .collect();
+
+        let mut deployed = Vec::with_capacity(results.len());
+        let mut errors = Vec::new();
+        for (destination, result) in results {
+            match result {
+                Ok(()) => deployed.push(destination),
+                Err(e) => errors.push((destination, e)),
+            }
+        }
+
+        if !errors.is_empty() {
+            println!("{} file(s) failed to deploy:", errors.len());
+            for (path, e) in &errors {
+                println!("  {}: {}", path.display(), e);
+            }
+            println!("Rolling back {} file(s) deployed this run", deployed.len());
+            for path in deployed {
+                let _ = fs::remove_file(install_dir.join(path));
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "{} file(s) failed to deploy; install rolled back",
+                    errors.len()
+                ),
+            )
+            .into());
+        }
+
+        // Merge rather than overwrite: `uninstall` re-adds paths it failed
+        // to delete back into `config.deployed` so a later deploy retries
+        // them. Replacing the whole vec here would forget those leftovers
+        // the moment the mod that used to own one stops winning, even
+        // though the file is still sitting undeleted on disk.
+        self.config.deployed.extend(deployed);
+        self.config.deployed.sort();
+        self.config.deployed.dedup();
+        Ok(())
+    }
+    /// Deploy a single file, falling back from a hard link to a plain copy
+    /// when the source and destination live on different filesystems
+    /// (`EXDEV`), which hard links can't span. Assumes `install_path`'s
+    /// parent directory already exists.
+    fn deploy_file(
+        source: &Path,
+        install_path: &Path,
+        method: DeploymentMethod,
+        warned_cross_device: &AtomicBool,
+    ) -> crate::Result<()> {
+        if install_path.exists() {
+            fs::remove_file(install_path)?;
+        }
+        match method {
+            DeploymentMethod::Hardlink => {
+                if let Err(e) = fs::hard_link(source, install_path) {
+                    if !is_cross_device(&e) {
+                        return Err(e.into());
+                    }
+                    if warned_cross_device
+                        .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+                        .is_ok()
+                    {
+                        println!(
+                            "Warning: mod library and game folder are on different \
+                            filesystems, falling back to copying instead of hard-linking"
+                        );
+                    }
+                    fs::copy(source, install_path)?;
+                }
+            }
+            DeploymentMethod::Symlink => {
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(source, install_path)?;
+                #[cfg(windows)]
+                std::os::windows::fs::hardlink(source, install_path)?;
+            }
+            DeploymentMethod::Copy => {
+                fs::copy(source, install_path)?;
+            }
+        }
+        Ok(())
+    }
+    /// Append any plugin discovered in an enabled mod's files that isn't
+    /// already in the stored load order, masters first so newly added
+    /// plugins still respect engine load requirements relative to each
+    /// other. Also disables (but doesn't remove) any stored entry whose
+    /// backing mod is no longer enabled, so a disabled or removed mod's
+    /// plugin stops being written to the plugins file even though it's
+    /// still undeployed; re-enabling the mod doesn't automatically revive
+    /// it, since the user may have disabled the plugin on purpose.
+    fn sync_load_order(&mut self) -> crate::Result<()> {
+        let mut discovered = Vec::new();
+        for (_, mm) in &self.config.mods {
+            if mm.enabled {
+                for mapping in mm.file_mappings()? {
+                    if let Some(name) = plugin_file_name(&mapping.destination) {
+                        if !discovered.contains(&name) {
+                            discovered.push(name);
                         }
                     }
                 }
             }
         }
+        let discovered_set: HashSet<&str> = discovered.iter().map(String::as_str).collect();
+        for entry in &mut self.config.load_order {
+            if !discovered_set.contains(entry.name.as_str()) {
+                entry.enabled = false;
+            }
+        }
+        let known: HashSet<&str> = self
+            .config
+            .load_order
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        let mut new_masters = Vec::new();
+        let mut new_esps = Vec::new();
+        for name in discovered {
+            if known.contains(name.as_str()) {
+                continue;
+            }
+            if is_master(&name) {
+                new_masters.push(name);
+            } else {
+                new_esps.push(name);
+            }
+        }
+        // New masters go right after the last master already in the
+        // stored order (or at the front if there is none) so the
+        // master-before-esp invariant holds across the whole list, not
+        // just within this sync's newly discovered plugins.
+        let insert_at = self
+            .config
+            .load_order
+            .iter()
+            .rposition(|p| is_master(&p.name))
+            .map_or(0, |i| i + 1);
+        for (offset, name) in new_masters.into_iter().enumerate() {
+            self.config.load_order.insert(
+                insert_at + offset,
+                PluginEntry {
+                    name,
+                    enabled: true,
+                },
+            );
+        }
+        for name in new_esps {
+            self.config.load_order.push(PluginEntry {
+                name,
+                enabled: true,
+            });
+        }
+        Ok(())
+    }
+    /// Enable or disable a single plugin independently of the mod that
+    /// owns it.
+    pub fn set_plugin_enabled(&mut self, name: &str, enabled: bool) -> crate::Result<()> {
+        self.config
+            .load_order
+            .iter_mut()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+            .map(|p| p.enabled = enabled)
+            .ok_or_else(|| crate::Error::UnknownMod(name.into()))
+    }
+    /// Move a plugin to `to_index` in the load order, shifting the plugins
+    /// between its old and new position.
+    pub fn move_plugin(&mut self, name: &str, to_index: usize) -> crate::Result<()> {
+        let from = self
+            .config
+            .load_order
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| crate::Error::UnknownMod(name.into()))?;
+        let entry = self.config.load_order.remove(from);
+        let to_index = to_index.min(self.config.load_order.len());
+        self.config.load_order.insert(to_index, entry);
         Ok(())
     }
     pub fn write_plugins(&mut self) -> crate::Result<()> {
+        self.sync_load_order()?;
         if let Some(plugins) = &self.config.plugins_file {
             let mut file = File::create(plugins)?;
-            for (_, mm) in &self.config.mods {
-                if mm.enabled {
-                    for path in mm.part_paths() {
-                        for entry in WalkDir::new(path).into_iter().filter_map(Result::ok) {
-                            if let Some(ext) = entry.path().extension() {
-                                if ["esp", "esm", "esl"].contains(&ext.to_string_lossy().as_ref()) {
-                                    writeln!(
-                                        file,
-                                        "*{}",
-                                        entry.path().file_name().unwrap().to_string_lossy()
-                                    )?;
-                                }
-                            }
-                        }
-                    }
+            for entry in &self.config.load_order {
+                if entry.enabled {
+                    writeln!(file, "*{}", entry.name)?;
                 }
             }
         }
@@ -326,6 +765,81 @@ impl Game {
         println!("Deployed");
         Ok(())
     }
+    /// Snapshot the current mod order, enabled flags, and plugin load order
+    /// into a new named profile, and make it the active one.
+    pub fn profile_new(&mut self, name: String) -> crate::Result<()> {
+        let profile = Profile {
+            mod_order: self.config.mods.keys().cloned().collect(),
+            enabled: self
+                .config
+                .mods
+                .iter()
+                .map(|(mod_name, mm)| (mod_name.clone(), mm.enabled))
+                .collect(),
+            load_order: self.config.load_order.clone(),
+        };
+        self.save_profile(&name, &profile)?;
+        self.config.active_profile = Some(name);
+        Ok(())
+    }
+    /// The names of every profile saved for this game.
+    pub fn profile_list(&self) -> crate::Result<Vec<String>> {
+        let dir = library::game_dir(&self.name)?.join(PROFILES_DIR);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names: Vec<String> = fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                entry
+                    .path()
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+    /// Switch to `name`, reordering and re-enabling `config.mods` and
+    /// replacing the plugin load order to match, then re-deploy. Mods the
+    /// profile doesn't know about are kept, appended after the profile's
+    /// own order.
+    pub fn profile_switch(&mut self, name: &str) -> crate::Result<()> {
+        let profile = self.load_profile(name)?;
+        let mut reordered = IndexMap::new();
+        for mod_name in &profile.mod_order {
+            if let Some((key, mut mm)) = self.config.mods.shift_remove_entry(mod_name) {
+                mm.enabled = profile.enabled.get(mod_name).copied().unwrap_or(mm.enabled);
+                reordered.insert(key, mm);
+            }
+        }
+        for (mod_name, mm) in self.config.mods.drain(..) {
+            reordered.insert(mod_name, mm);
+        }
+        self.config.mods = reordered;
+        self.config.load_order = profile.load_order;
+        self.config.active_profile = Some(name.to_string());
+        self.deploy()
+    }
+    pub fn profile_delete(&mut self, name: &str) -> crate::Result<()> {
+        fs::remove_file(profile_file(&self.name, name)?)?;
+        if self.config.active_profile.as_deref() == Some(name) {
+            self.config.active_profile = None;
+        }
+        Ok(())
+    }
+    fn save_profile(&self, name: &str, profile: &Profile) -> crate::Result<()> {
+        let path = profile_file(&self.name, name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let string = toml::to_string_pretty(profile)?;
+        fs::write(path, string).map_err(Into::into)
+    }
+    fn load_profile(&self, name: &str) -> crate::Result<Profile> {
+        let bytes = fs::read(profile_file(&self.name, name)?)?;
+        toml::from_slice(&bytes).map_err(Into::into)
+    }
 }
 
 impl Drop for Game {
@@ -336,9 +850,24 @@ impl Drop for Game {
     }
 }
 
-fn differ<P>(top: &P) -> impl Fn(&'_ Path) -> Option<PathBuf> + '_
-where
-    P: AsRef<Path>,
-{
-    move |path| diff_paths(path, top)
+/// Remove every directory under `root` left empty after uninstalling
+/// files, deepest first so a parent that only held now-empty children is
+/// itself pruned in the same pass.
+fn prune_empty_dirs(root: &Path) -> crate::Result<()> {
+    if !root.exists() {
+        return Ok(());
+    }
+    let mut dirs: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_dir() && entry.path() != root)
+        .map(DirEntry::into_path)
+        .collect();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    for dir in dirs {
+        if fs::read_dir(&dir)?.next().is_none() {
+            fs::remove_dir(&dir)?;
+        }
+    }
+    Ok(())
 }